@@ -0,0 +1,94 @@
+use std::str::Chars;
+
+/// A forward/backward character cursor over a source buffer.
+///
+/// `Cursor` wraps a [`Chars`] iterator and keeps every consumed character in a
+/// `history` buffer so it can cheaply seek backwards. It tracks the byte `pos`
+/// of the next character together with the 1-based `line`/`col` of that
+/// character, advancing them exactly once per real character so span offsets and
+/// `TextSpan.line` stay accurate. `peek` is O(1) and `peek_nth(n)` is O(n) in
+/// the lookahead distance only: both clone the underlying `Chars` (a thin slice
+/// iterator) rather than walking the input from the start.
+pub struct Cursor<'a> {
+    source: &'a str,
+    chars: Chars<'a>,
+    history: Vec<char>,
+    pos: usize,
+    line: usize,
+    col: usize,
+    /// Column reached at the end of each completed line, so a backward seek
+    /// across a `\n` can restore the previous line's final column.
+    line_lengths: Vec<usize>,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            chars: source.chars(),
+            history: Vec::new(),
+            pos: 0,
+            line: 1,
+            col: 1,
+            line_lengths: Vec::new(),
+        }
+    }
+
+    /// Byte offset of the next character to be consumed.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// 1-based line of the next character to be consumed.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// 1-based column of the next character to be consumed.
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    /// Consume and return the current character, advancing `pos`/`line`/`col`.
+    pub fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.history.push(c);
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line_lengths.push(self.col);
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    /// Return the current character without consuming it.
+    pub fn peek(&self) -> Option<char> {
+        self.chars.clone().next()
+    }
+
+    /// Return the character `n` positions ahead of the cursor (`0` == [`peek`]).
+    ///
+    /// [`peek`]: Cursor::peek
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.clone().nth(n)
+    }
+
+    /// Step the cursor back over the last `n` consumed characters, restoring
+    /// `pos`, `line`, and `col`. Seeking past the start stops at the start.
+    pub fn seek_back(&mut self, n: usize) {
+        for _ in 0..n {
+            let Some(c) = self.history.pop() else { break };
+            self.pos -= c.len_utf8();
+            if c == '\n' {
+                self.line -= 1;
+                self.col = self.line_lengths.pop().unwrap_or(1);
+            } else {
+                self.col -= 1;
+            }
+        }
+        self.chars = self.source[self.pos..].chars();
+    }
+}
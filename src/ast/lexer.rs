@@ -1,27 +1,54 @@
-use std::cmp;
+use crate::ast::cursor::Cursor;
 
-#[derive(Debug)]
-enum TokenKind {
+#[derive(Debug, PartialEq)]
+pub enum TokenKind<'src> {
     Data,
     Literal,
     RightParen,
     LeftParen,
     Equals,
     Eof,
-    Bad,
-    Error,
+    /// A numeric literal. `radix` is the base implied by any `0x`/`0o`/`0b`
+    /// prefix (10 otherwise) and `raw` is the unparsed text including sign and
+    /// separators.
+    Number {
+        radix: u32,
+        is_float: bool,
+        raw: &'src str,
+    },
+    /// A character that starts no known token. Still emitted as a real token so
+    /// the stream stays aligned; the problem is recorded as a [`Diagnostic`].
+    Unknown,
+}
+
+/// Error flag attached to an otherwise-real token whose lexing ran into a
+/// problem the lexer recovered from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LexError {
+    UnterminatedString,
+    InvalidChar,
+    MalformedNumber,
 }
 
+/// A problem found while lexing, with the position needed to report it.
 #[derive(Debug)]
-pub struct TextSpan {
+pub struct Diagnostic<'src> {
+    pub message: String,
+    pub line: usize,
+    pub col: usize,
+    pub span: TextSpan<'src>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TextSpan<'src> {
     start: usize,
     end: usize,
     line: usize,
-    literal: String,
+    literal: &'src str,
 }
 
-impl TextSpan {
-    pub fn new(start: usize, end: usize, line: usize, literal: String) -> Self {
+impl<'src> TextSpan<'src> {
+    pub fn new(start: usize, end: usize, line: usize, literal: &'src str) -> Self {
         Self {
             start,
             end,
@@ -33,242 +60,357 @@ impl TextSpan {
     pub fn len(&self) -> usize {
         self.end - self.start
     }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn literal(&self) -> &'src str {
+        self.literal
+    }
+
+    /// Materialize an owned copy of the borrowed literal for callers that need
+    /// to outlive the source buffer.
+    pub fn literal_owned(&self) -> String {
+        self.literal.to_string()
+    }
 }
 
 #[derive(Debug)]
-pub struct Token {
-    kind: TokenKind,
-    span: TextSpan,
+pub struct Token<'src> {
+    kind: TokenKind<'src>,
+    span: TextSpan<'src>,
+    error: Option<LexError>,
 }
 
-impl Token {
-    fn new(kind: TokenKind, span: TextSpan) -> Token {
-        Token { kind, span }
+impl<'src> Token<'src> {
+    fn new(kind: TokenKind<'src>, span: TextSpan<'src>) -> Token<'src> {
+        Token {
+            kind,
+            span,
+            error: None,
+        }
+    }
+
+    fn with_error(kind: TokenKind<'src>, span: TextSpan<'src>, error: LexError) -> Token<'src> {
+        Token {
+            kind,
+            span,
+            error: Some(error),
+        }
+    }
+
+    pub fn kind(&self) -> &TokenKind<'src> {
+        &self.kind
+    }
+
+    pub fn span(&self) -> &TextSpan<'src> {
+        &self.span
+    }
+
+    /// The error flag set when this token was lexed with a recovered problem.
+    pub fn error(&self) -> Option<LexError> {
+        self.error
     }
 }
 
-pub(crate) struct Lexer {
-    input: String,
-    current_pos: usize,
-    current_line: usize,
+pub(crate) struct Lexer<'a> {
+    source: &'a str,
+    cursor: Cursor<'a>,
+    diagnostics: Vec<Diagnostic<'a>>,
+    emitted_eof: bool,
 }
 
-impl Lexer {
-    pub fn new(input: String) -> Self {
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
         Self {
-            input,
-            current_pos: 0,
-            current_line: 1,
+            source: input,
+            cursor: Cursor::new(input),
+            diagnostics: Vec::new(),
+            emitted_eof: false,
         }
     }
-    
-    pub fn tokenize(mut self) -> Vec<Token> {
-        if self.current_pos >= self.input.len() {
-            // return vec!(Token::new(TokenKind::Error, TextSpan::new(self.current_pos, self.current_pos, "Input already tokenized".to_string())));
-        }
 
-        self.skip_whitespace();
-        self.skip_comments();
+    /// Lex the whole input, returning the tokens alongside every diagnostic
+    /// collected. This is a thin wrapper that drains [`next_token`] into a
+    /// `Vec`; the trailing `Eof` token is included.
+    ///
+    /// [`next_token`]: Lexer::next_token
+    pub fn tokenize(mut self) -> (Vec<Token<'a>>, Vec<Diagnostic<'a>>) {
+        let tokens: Vec<Token> = self.by_ref().collect();
+        (tokens, self.diagnostics)
+    }
 
-        let mut vec: Vec<Token> = Vec::new();
-        
-        while let Some(c) = self.consume() {
-            
-            if c.is_whitespace() {
-                continue;
-            }
+    /// Produce the next token, or `None` once the stream is exhausted. A single
+    /// `Eof` token is emitted at the end of input before `None` is returned.
+    /// Lexing never aborts: malformed input still yields a token so later stages
+    /// stay aligned, and the problem is recorded as a [`Diagnostic`].
+    pub fn next_token(&mut self) -> Option<Token<'a>> {
+        self.skip_trivia();
 
-            if c == '(' {
-                vec.push(Token::new(
-                    TokenKind::LeftParen,
-                    TextSpan::new(self.current_pos, self.current_pos, self.current_line, "(".to_string()),
-                ));
-                
-                continue;
-            }
+        let start = self.cursor.pos();
+        let line = self.cursor.line();
+        let col = self.cursor.col();
 
-            if c == ')' {
-                vec.push(Token::new(
-                    TokenKind::RightParen,
-                    TextSpan::new(self.current_pos, self.current_pos, self.current_line, ")".to_string()),
-                ));
-                
-                continue;
+        let Some(c) = self.cursor.peek() else {
+            if self.emitted_eof {
+                return None;
             }
-            
-            if c == '=' {
-                vec.push(Token::new(
-                    TokenKind::Equals,
-                    TextSpan::new(self.current_pos, self.current_pos, self.current_line, "=".to_string()),
-                ))
+            self.emitted_eof = true;
+            return Some(self.single(TokenKind::Eof, start, line));
+        };
+
+        if c == '(' {
+            self.cursor.bump();
+            return Some(self.single(TokenKind::LeftParen, start, line));
+        }
+
+        if c == ')' {
+            self.cursor.bump();
+            return Some(self.single(TokenKind::RightParen, start, line));
+        }
+
+        if c == '=' {
+            self.cursor.bump();
+            return Some(self.single(TokenKind::Equals, start, line));
+        }
+
+        if c == '"' {
+            return Some(self.string_literal_tokenize(start, line, col));
+        }
+
+        if c.is_ascii_digit()
+            || ((c == '+' || c == '-')
+                && self.cursor.peek_nth(1).is_some_and(|d| d.is_ascii_digit()))
+        {
+            return Some(self.number_tokenize(start, line, col));
+        }
+
+        if c.is_alphabetic() {
+            return Some(self.greedy_tokenize(start, line));
+        }
+
+        // Unknown character: flag it and keep going so a single stray byte
+        // doesn't abort the whole pass.
+        self.cursor.bump();
+        let end = self.cursor.pos();
+        let span = TextSpan::new(start, end, line, self.slice(start, end));
+        self.diagnostics.push(Diagnostic {
+            message: format!("unexpected character `{}`", c),
+            line,
+            col,
+            span: span.clone(),
+        });
+        Some(Token::with_error(TokenKind::Unknown, span, LexError::InvalidChar))
+    }
+
+    fn single(&self, kind: TokenKind<'a>, start: usize, line: usize) -> Token<'a> {
+        let end = self.cursor.pos();
+        Token::new(kind, TextSpan::new(start, end, line, self.slice(start, end)))
+    }
+
+    fn greedy_tokenize(&mut self, start: usize, line: usize) -> Token<'a> {
+        while let Some(c) = self.cursor.peek() {
+            if !c.is_alphanumeric() {
+                break;
             }
+            self.cursor.bump();
+        }
+
+        let end = self.cursor.pos();
+        let literal = self.slice(start, end);
+        let kind = if literal.eq_ignore_ascii_case("data") {
+            TokenKind::Data
+        } else {
+            TokenKind::Literal
+        };
+        Token::new(kind, TextSpan::new(start, end, line, literal))
+    }
+
+    fn string_literal_tokenize(&mut self, start: usize, line: usize, col: usize) -> Token<'a> {
+        self.cursor.bump(); // opening quote
 
+        while let Some(c) = self.cursor.peek() {
+            self.cursor.bump();
             if c == '"' {
-                self.string_literal_tokenize(self.current_char() == Some('"') || self.peek_char() == Some('"'))
+                let end = self.cursor.pos();
+                // The span covers the quotes, but the literal text drops the
+                // surrounding delimiters so consumers get `hello`, not `"hello"`.
+                return Token::new(
+                    TokenKind::Literal,
+                    TextSpan::new(start, end, line, self.slice(start + 1, end - 1)),
+                );
             }
-            
-            if c.is_alphabetic() {
-                vec.push(self.greedy_tokenize(c));
-                continue
-            }
-            
         }
 
-        vec
+        // Ran off the end of the input without a closing quote: keep the text
+        // we did read as a literal and flag it.
+        let end = self.cursor.pos();
+        let span = TextSpan::new(start, end, line, self.slice(start + 1, end));
+        self.diagnostics.push(Diagnostic {
+            message: "unterminated string literal".to_string(),
+            line,
+            col,
+            span: span.clone(),
+        });
+        Token::with_error(TokenKind::Literal, span, LexError::UnterminatedString)
     }
-    
-    fn greedy_tokenize(&mut self, c: char) -> Token {
-        let mut buffer = c.to_string();
-        let start = self.current_pos;
-        while let Some(c) = self.consume() {
-            buffer.push(c);
-
-            match self.current_char() {
-                Some(peek) if !peek.is_alphanumeric() => {
-                    let end = self.current_pos;
-                    if buffer.to_lowercase() == "data" {
-                        return Token::new(TokenKind::Data, TextSpan::new(start, end, self.current_line, buffer));
-                    }
-                    return Token::new(TokenKind::Literal, TextSpan::new(start, end, self.current_line, buffer));
-                }
-                None => {
-                    
-                    // todo make sure there's no need for #consume
-                    
-                    let end = self.current_pos;
-                    if buffer.to_lowercase() == "data" {
-                        return Token::new(TokenKind::Data, TextSpan::new(start, end, self.current_line, buffer));
-                    }
-                    return Token::new(TokenKind::Literal, TextSpan::new(start, end, self.current_line, buffer));
+
+    fn number_tokenize(&mut self, start: usize, line: usize, col: usize) -> Token<'a> {
+        // Optional leading sign.
+        if matches!(self.cursor.peek(), Some('+') | Some('-')) {
+            self.cursor.bump();
+        }
+
+        // A radix prefix is only recognised when the leading digit is `0`.
+        let mut radix = 10;
+        if self.cursor.peek() == Some('0') {
+            radix = match self.cursor.peek_nth(1) {
+                Some('x') | Some('X') => 16,
+                Some('o') | Some('O') => 8,
+                Some('b') | Some('B') => 2,
+                _ => 10,
+            };
+            if radix != 10 {
+                self.cursor.bump(); // '0'
+                self.cursor.bump(); // radix letter
+            }
+        }
+
+        let mut is_float = false;
+        let (saw_digit, mut dangling) = self.consume_digit_run(radix);
+        // A recognised radix prefix with no digits behind it (`0x`, `0o`, `0b`).
+        let empty_radix = radix != 10 && !saw_digit;
+        let mut empty_exponent = false;
+
+        if radix == 10 {
+            // A single decimal point followed by fractional digits.
+            if self.cursor.peek() == Some('.')
+                && self.cursor.peek_nth(1).is_some_and(|d| d.is_ascii_digit())
+            {
+                is_float = true;
+                self.cursor.bump(); // '.'
+                let (_, sep) = self.consume_digit_run(10);
+                dangling = sep;
+            }
+
+            // A single exponent clause with optional sign.
+            if matches!(self.cursor.peek(), Some('e') | Some('E')) {
+                is_float = true;
+                self.cursor.bump();
+                if matches!(self.cursor.peek(), Some('+') | Some('-')) {
+                    self.cursor.bump();
                 }
-                Some(_) => (),
+                let (saw_digit, sep) = self.consume_digit_run(10);
+                dangling = sep;
+                empty_exponent = !saw_digit;
             }
         }
 
+        let end = self.cursor.pos();
+        let raw = self.slice(start, end);
+        let span = TextSpan::new(start, end, line, raw);
+
+        let message = if empty_radix {
+            Some("radix prefix has no digits")
+        } else if empty_exponent {
+            Some("exponent has no digits")
+        } else if dangling {
+            Some("digit separator must be between digits")
+        } else {
+            None
+        };
+
+        if let Some(message) = message {
+            self.diagnostics.push(Diagnostic {
+                message: message.to_string(),
+                line,
+                col,
+                span: span.clone(),
+            });
+            return Token::with_error(
+                TokenKind::Number {
+                    radix,
+                    is_float,
+                    raw,
+                },
+                span,
+                LexError::MalformedNumber,
+            );
+        }
+
         Token::new(
-            TokenKind::Error,
-            TextSpan::new(
-                start,
-                self.current_pos,
-                self.current_line,
-                "Literal does not terminate".to_string(),
-            ),
+            TokenKind::Number {
+                radix,
+                is_float,
+                raw,
+            },
+            span,
         )
     }
-    
-    fn string_literal_tokenize(&mut self, is_multi_line: bool) {
-        if is_multi_line {
-            self.consume();
-            self.consume();
+
+    /// Consume a run of digits valid for `radix`, allowing `_` separators.
+    /// Returns whether any digit was seen and whether the run ended on a
+    /// separator (a dangling `_`).
+    fn consume_digit_run(&mut self, radix: u32) -> (bool, bool) {
+        let mut saw_digit = false;
+        let mut last_was_separator = false;
+        loop {
+            match self.cursor.peek() {
+                Some('_') => {
+                    self.cursor.bump();
+                    last_was_separator = true;
+                }
+                Some(c) if c.is_digit(radix) => {
+                    self.cursor.bump();
+                    saw_digit = true;
+                    last_was_separator = false;
+                }
+                _ => break,
+            }
         }
-        let mut buffer = String::new();
-        while let Some(c) = self.consume() {
-            buffer.push(c);
-            
-            if self.current_char() == Some('"') {
+        (saw_digit, last_was_separator)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            let before = self.cursor.pos();
+            self.skip_whitespace();
+            self.skip_comments();
+            if self.cursor.pos() == before {
                 break;
             }
         }
     }
-    
+
     fn skip_whitespace(&mut self) {
-        while self.current_char().unwrap().is_whitespace() && self.current_pos < self.input.len() {
-            self.consume();
+        while let Some(c) = self.cursor.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.cursor.bump();
         }
     }
-    
+
     fn skip_comments(&mut self) {
-        while let Some(c) = self.current_char()
-            && let Some(peek) = self.peek_char()
-        {
-            if c != '/' || peek != '/' {
-                break;
-            }
-            'exhaust_comment: while let Some(c) = self.consume() {
+        while self.cursor.peek() == Some('/') && self.cursor.peek_nth(1) == Some('/') {
+            'exhaust_comment: while let Some(c) = self.cursor.bump() {
                 if c == '\n' {
                     break 'exhaust_comment;
                 }
             }
         }
     }
-    
-    fn current_char(&self) -> Option<char> {
-        self.input.chars().nth(self.current_pos)
-    }
-    
-    fn peek_char(&self) -> Option<char> {
-        self.input.chars().nth(self.current_pos + 1)
-    }
-    
-    fn peek_char_by(&self, by: usize) -> Option<char> {
-        self.input.chars().nth(self.current_pos + cmp::max(by, 1))
+
+    fn slice(&self, start: usize, end: usize) -> &'a str {
+        &self.source[start..end]
     }
-    
-    fn consume(&mut self) -> Option<char> {
-        if self.current_pos >= self.input.len() {
-            return None;
-        }
-        if self.peek_char() == Some('\n') {
-            self.current_pos += 1;
-            self.current_line += 1;
-        }
-        let c = self.current_char();
-        self.current_pos += 1;
-        c
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token()
     }
-    
-    // pub fn next_token(&mut self) -> Option<Token> {
-    //     self.skip_whitespace();
-    //
-    //     if self.current_pos == self.input.len() {
-    //         return Some(Token::new(
-    //             Eof,
-    //             TextSpan::new(self.current_pos, self.current_pos, String::new()),
-    //         ));
-    //     }
-    //
-    //     let start = self.current_pos;
-    //     let c = self.current_char();
-    //     c.map(|c| {
-    //         let start = self.current_pos;
-    //         let mut kind = TokenKind::Bad;
-    //         if c.is_digit(10) {
-    //             let number = self.consume_number();
-    //             kind = TokenKind::Number(number)
-    //         }
-    //         let end = self.current_pos;
-    //         let literal = self.input[start..end].to_string();
-    //         let span = TextSpan::new(start, end, literal);
-    //         Token::new(kind, span)
-    //     })
-    // }
-    //
-    // fn is_number_start(c: &char) -> bool {
-    //     c.is_digit(10)
-    // }
-    //
-    // fn consume_number(&mut self) -> i64 {
-    //     let mut number: i64 = 0;
-    //
-    //     'greedy: loop {
-    //         match self.current_char() {
-    //             None => break 'greedy,
-    //             Some(_) => {
-    //                 if !self.current_char().unwrap().is_digit(10) {
-    //                     println!("NOOOO: {:?}", self.current_char());
-    //                     break 'greedy;
-    //                 }
-    //             }
-    //         }
-    //
-    //         let digit = self.consume().unwrap().to_digit(10);
-    //
-    //         println!("{:?}", digit);
-    //
-    //         number = number * 10 + digit.unwrap() as i64;
-    //     }
-    //
-    //     number
-    // }
-    
 }
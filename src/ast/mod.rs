@@ -0,0 +1,3 @@
+pub mod cursor;
+pub mod lexer;
+pub mod parser;
@@ -0,0 +1,141 @@
+use crate::ast::lexer::{TextSpan, Token, TokenKind};
+
+/// A parsed `data(...)` block: the assignments between its parentheses.
+#[derive(Debug)]
+pub struct DataBlock {
+    pub assignments: Vec<Assignment>,
+}
+
+/// A single `name = value` entry inside a [`DataBlock`].
+#[derive(Debug)]
+pub struct Assignment {
+    pub name: String,
+    pub value: Expr,
+}
+
+/// The value side of an [`Assignment`]: a bare literal, a numeric literal, or a
+/// nested block.
+#[derive(Debug)]
+pub enum Expr {
+    Literal(String),
+    Number {
+        raw: String,
+        radix: u32,
+        is_float: bool,
+    },
+    Block(DataBlock),
+}
+
+/// An error raised while parsing, carrying the span of the offending token.
+#[derive(Debug)]
+pub struct ParseError<'src> {
+    pub message: String,
+    pub span: TextSpan<'src>,
+}
+
+/// Recursive-descent parser over the token stream produced by the lexer.
+pub struct Parser<'src> {
+    tokens: Vec<Token<'src>>,
+    current_pos: usize,
+}
+
+impl<'src> Parser<'src> {
+    pub fn new(tokens: Vec<Token<'src>>) -> Self {
+        Self {
+            tokens,
+            current_pos: 0,
+        }
+    }
+
+    /// Parse the whole token stream into a list of top-level data blocks.
+    pub fn parse_program(&mut self) -> Result<Vec<DataBlock>, ParseError<'src>> {
+        let mut blocks = Vec::new();
+        while !self.at_end() {
+            match self.peek().map(Token::kind) {
+                Some(TokenKind::Data) => blocks.push(self.parse_data_block()?),
+                _ => return Err(self.error("expected a `data` block")),
+            }
+        }
+        Ok(blocks)
+    }
+
+    fn parse_data_block(&mut self) -> Result<DataBlock, ParseError<'src>> {
+        self.expect(&TokenKind::Data)?;
+        self.expect(&TokenKind::LeftParen)?;
+
+        let mut assignments = Vec::new();
+        while !self.check(&TokenKind::RightParen) && !self.at_end() {
+            assignments.push(self.parse_assignment()?);
+        }
+
+        self.expect(&TokenKind::RightParen)?;
+        Ok(DataBlock { assignments })
+    }
+
+    fn parse_assignment(&mut self) -> Result<Assignment, ParseError<'src>> {
+        let name = self.expect(&TokenKind::Literal)?.span().literal().to_string();
+        self.expect(&TokenKind::Equals)?;
+        let value = self.parse_expr()?;
+        Ok(Assignment { name, value })
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, ParseError<'src>> {
+        match self.peek().map(Token::kind) {
+            Some(TokenKind::Literal) => {
+                let literal = self.advance().unwrap().span().literal().to_string();
+                Ok(Expr::Literal(literal))
+            }
+            Some(TokenKind::Number { radix, is_float, raw }) => {
+                let (radix, is_float, raw) = (*radix, *is_float, *raw);
+                self.advance();
+                Ok(Expr::Number {
+                    raw: raw.to_string(),
+                    radix,
+                    is_float,
+                })
+            }
+            Some(TokenKind::Data) => Ok(Expr::Block(self.parse_data_block()?)),
+            _ => Err(self.error("expected a literal, number, or nested `data` block")),
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind<'src>) -> Result<&Token<'src>, ParseError<'src>> {
+        if self.check(kind) {
+            Ok(self.advance().unwrap())
+        } else {
+            Err(self.error(&format!("expected {:?}", kind)))
+        }
+    }
+
+    fn check(&self, kind: &TokenKind<'src>) -> bool {
+        self.peek().is_some_and(|t| t.kind() == kind)
+    }
+
+    fn peek(&self) -> Option<&Token<'src>> {
+        self.tokens.get(self.current_pos)
+    }
+
+    fn advance(&mut self) -> Option<&Token<'src>> {
+        if self.current_pos >= self.tokens.len() {
+            return None;
+        }
+        let consumed = self.current_pos;
+        self.current_pos += 1;
+        self.tokens.get(consumed)
+    }
+
+    fn at_end(&self) -> bool {
+        matches!(self.peek().map(Token::kind), None | Some(TokenKind::Eof))
+    }
+
+    fn error(&self, message: &str) -> ParseError<'src> {
+        let span = self
+            .peek()
+            .map(|t| t.span().clone())
+            .unwrap_or_else(|| TextSpan::new(0, 0, 0, ""));
+        ParseError {
+            message: message.to_string(),
+            span,
+        }
+    }
+}
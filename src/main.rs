@@ -2,6 +2,7 @@ use std::fs;
 use std::env;
 
 use crate::ast::lexer::Lexer;
+use crate::ast::parser::Parser;
 
 mod ast;
 
@@ -21,10 +22,26 @@ fn main() {
             std::process::exit(1);
         });
     
-    let lexer = Lexer::new(input);
-    let tokens = lexer.tokenize();
-    
-    for token in tokens {
-        println!("{:?}", token);
+    let lexer = Lexer::new(&input);
+    let (tokens, diagnostics) = lexer.tokenize();
+
+    for diagnostic in &diagnostics {
+        eprintln!(
+            "{}:{}: {}",
+            diagnostic.line, diagnostic.col, diagnostic.message
+        );
+    }
+
+    let mut parser = Parser::new(tokens);
+    match parser.parse_program() {
+        Ok(program) => {
+            for block in program {
+                println!("{:?}", block);
+            }
+        }
+        Err(err) => {
+            eprintln!("Parse error: {}", err.message);
+            std::process::exit(1);
+        }
     }
 }